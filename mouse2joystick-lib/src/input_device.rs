@@ -1,12 +1,32 @@
-use std::{os::fd::AsRawFd, path::PathBuf};
+use std::{collections::HashMap, os::fd::AsRawFd, path::PathBuf};
 
-use evdev::{Device, FetchEventsSynced};
+use evdev::{AbsoluteAxisType, Device, FetchEventsSynced};
 use mio::{unix::SourceFd, Interest, Registry, Token};
 use nix::fcntl::{FcntlArg, OFlag};
 
+/// Per-axis normalization parameters, read from the device's own `AbsInfo` so a controller with a
+/// different range or center than the ones `JoystickConfig` was tuned for still maps correctly.
+#[derive(Debug, Clone, Copy)]
+pub struct AxisCalibration {
+    pub center: f32,
+    pub half_amplitude: f32,
+    pub dead_zone: f32,
+}
+
+impl Default for AxisCalibration {
+    fn default() -> Self {
+        Self {
+            center: 510.0,
+            half_amplitude: 300.0,
+            dead_zone: 50.0,
+        }
+    }
+}
+
 pub struct InputDevice {
     pub path: PathBuf,
     pub device: Device,
+    pub axis_calibration: HashMap<AbsoluteAxisType, AxisCalibration>,
 }
 
 impl InputDevice {
@@ -19,7 +39,38 @@ impl InputDevice {
         let raw_fd = device.as_raw_fd();
         //Make is non blocking
         nix::fcntl::fcntl(raw_fd, FcntlArg::F_SETFL(OFlag::O_NONBLOCK))?;
-        Ok(Self { path, device })
+
+        let axis_calibration = Self::read_axis_calibration(&device)?;
+
+        Ok(Self {
+            path,
+            device,
+            axis_calibration,
+        })
+    }
+
+    fn read_axis_calibration(
+        device: &Device,
+    ) -> Result<HashMap<AbsoluteAxisType, AxisCalibration>, std::io::Error> {
+        let Some(supported) = device.supported_absolute_axes() else {
+            return Ok(HashMap::new());
+        };
+        let abs_state = device.get_abs_state()?;
+
+        Ok(supported
+            .iter()
+            .map(|axis| {
+                let info = &abs_state[axis.0 as usize];
+                (
+                    axis,
+                    AxisCalibration {
+                        center: (info.minimum + info.maximum) as f32 / 2.0,
+                        half_amplitude: (info.maximum - info.minimum) as f32 / 2.0,
+                        dead_zone: info.flat as f32,
+                    },
+                )
+            })
+            .collect())
     }
 
     pub fn events(&mut self) -> Result<FetchEventsSynced<'_>, std::io::Error> {
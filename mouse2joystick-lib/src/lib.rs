@@ -21,40 +21,162 @@ use mio::Token;
 pub struct Config {
     pub mouse: MouseConfig,
     pub joystick: JoystickConfig,
+    #[serde(default)]
+    pub buttons: ButtonConfig,
+    #[serde(default)]
+    pub device: DeviceConfig,
+}
+
+/// Name substrings used to allow or ignore devices enumerated by [`import_devices`].
+#[derive(serde::Deserialize, Default)]
+pub struct DeviceConfig {
+    #[serde(default)]
+    pub allow: Vec<String>,
+    #[serde(default)]
+    pub ignore: Vec<String>,
 }
 
 #[derive(serde::Deserialize)]
 pub struct MouseConfig {
     pub speed: f32,
+    /// Scroll speed applied to the right stick; `0.0` disables scroll emulation.
+    #[serde(default)]
+    pub scroll_speed: f32,
+    #[serde(default)]
+    pub mode: OutputMode,
+    #[serde(default = "default_screen_width")]
+    pub screen_width: i32,
+    #[serde(default = "default_screen_height")]
+    pub screen_height: i32,
 }
 
-#[derive(serde::Deserialize)]
+fn default_screen_width() -> i32 {
+    1920
+}
+
+fn default_screen_height() -> i32 {
+    1080
+}
+
+/// Selects between emitting `REL_X`/`REL_Y` deltas and driving an absolute `ABS_X`/`ABS_Y`
+/// pointer sized to `screen_width`/`screen_height` (kiosk / remote-desktop targets).
+#[derive(serde::Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputMode {
+    #[default]
+    Relative,
+    Absolute,
+}
+
+#[derive(serde::Deserialize, Default)]
+pub struct ButtonConfig {
+    #[serde(default)]
+    pub mappings: Vec<ButtonMapping>,
+}
+
+/// A single source button (by evdev key code) mapped to an output action.
+#[derive(serde::Deserialize, Clone, Copy)]
+pub struct ButtonMapping {
+    pub source: u16,
+    pub action: ButtonAction,
+}
+
+#[derive(serde::Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ButtonAction {
+    MouseLeft,
+    MouseRight,
+    MouseMiddle,
+    Key(u16),
+    /// Repeatedly press and release `action` at `interval_ms` for as long as the source button
+    /// stays held.
+    Turbo { action: ClickAction, interval_ms: u64 },
+    /// Press and release `action`, then do it again after `delay_ms`.
+    DoubleClick { action: ClickAction, delay_ms: u64 },
+}
+
+/// The click/keypress a [`ButtonAction::Turbo`] or [`ButtonAction::DoubleClick`] repeats.
+#[derive(serde::Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ClickAction {
+    MouseLeft,
+    MouseRight,
+    MouseMiddle,
+    Key(u16),
+}
+
+/// Stick-rotation and, optionally, manual overrides of the automatic per-axis calibration read
+/// from the device (see [`crate::input_device::AxisCalibration`]).
+#[derive(serde::Deserialize, Default)]
 pub struct JoystickConfig {
-    pub dead_zone: f32,
-    pub half_amplitude: f32,
-    pub offset: f32,
+    #[serde(default)]
+    pub dead_zone: Option<f32>,
+    #[serde(default)]
+    pub half_amplitude: Option<f32>,
+    #[serde(default)]
+    pub offset: Option<f32>,
     pub angle: f32,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
-            mouse: MouseConfig { speed: 900.0 },
+            mouse: MouseConfig {
+                speed: 900.0,
+                scroll_speed: 0.0,
+                mode: OutputMode::Relative,
+                screen_width: default_screen_width(),
+                screen_height: default_screen_height(),
+            },
             joystick: JoystickConfig {
-                dead_zone: 50.0,
-                half_amplitude: 300.0,
-                offset: 510.0,
+                dead_zone: None,
+                half_amplitude: None,
+                offset: None,
                 angle: -30.0,
             },
+            buttons: ButtonConfig::default(),
+            device: DeviceConfig::default(),
         }
     }
 }
 
+/// Names of the uinput nodes this process creates itself (see [`crate::uinput::UInputMouse`] and
+/// [`crate::uinput::UInputAbsoluteMouse`]). The absolute-mode device reports `ABS_X`/`ABS_Y` just
+/// like a real joystick, so the capability check below isn't enough to keep the tool from
+/// re-capturing its own synthesized output; always exclude these regardless of `allow`/`ignore`.
+const SELF_DEVICE_NAMES: [&str; 2] = ["Virtual mouse", "Virtual absolute mouse"];
+
+/// Whether `device` should be captured: it must match an `allow` entry (if any are configured),
+/// must not match any `ignore` entry, and must actually report `ABS_X`/`ABS_Y` so keyboards and
+/// the crate's own relative virtual mouse are never captured. A touchpad reports `ABS_X`/`ABS_Y`
+/// too, so it passes the capability check and is kept out only by `allow`/`ignore`.
+fn device_is_allowed(device: &evdev::Device, config: &DeviceConfig) -> bool {
+    let name = device.name().unwrap_or_default();
+
+    if SELF_DEVICE_NAMES.contains(&name) {
+        return false;
+    }
+    if config.ignore.iter().any(|pattern| name.contains(pattern.as_str())) {
+        return false;
+    }
+    if !config.allow.is_empty() && !config.allow.iter().any(|pattern| name.contains(pattern.as_str())) {
+        return false;
+    }
+
+    device.supported_absolute_axes().is_some_and(|axes| {
+        axes.contains(evdev::AbsoluteAxisType::ABS_X) && axes.contains(evdev::AbsoluteAxisType::ABS_Y)
+    })
+}
+
 pub fn import_devices(
     input_device_pool: &mut InputDevicePool,
     poll: &mut Poll,
+    config: &Config,
 ) -> std::io::Result<()> {
     for (path, device) in evdev::enumerate() {
+        if !device_is_allowed(&device, &config.device) {
+            continue;
+        }
         input_device_pool.insert(InputDevice::new_path_device(path, device)?, poll)?;
     }
     Ok(())
@@ -67,8 +189,9 @@ fn process_event(
     token: Token,
 ) -> Result<(), std::io::Error> {
     let Some(device) = input_device_pool.get_mut(token) else {return Ok(());};
+    let axis_calibration = device.axis_calibration.clone();
     for event in device.events()? {
-        vmouse_manager.map_event(event, &config.joystick);
+        vmouse_manager.map_event(event, &config.joystick, &config.buttons, &axis_calibration);
     }
     Ok(())
 }
@@ -92,7 +215,7 @@ pub extern "C" fn run_loop() {
         .expect("Cant register inotify for polling");
 
     let mut input_device_pool = InputDevicePool::new(1);
-    import_devices(&mut input_device_pool, &mut poll).expect("Can't populate");
+    import_devices(&mut input_device_pool, &mut poll, &config).expect("Can't populate");
 
     let mut vmouse_manager = VMouseManager::new(&config.mouse).expect("Can't create vmouse");
 
@@ -112,7 +235,7 @@ pub extern "C" fn run_loop() {
 
         for event in &events {
             match event.token() {
-                Token(0) => import_devices(&mut input_device_pool, &mut poll)
+                Token(0) => import_devices(&mut input_device_pool, &mut poll, &config)
                     .expect("Error while checking new devices"),
                 token if input_device_pool.contains(token) => {
                     match process_event(&mut input_device_pool, &mut vmouse_manager, &config, token)
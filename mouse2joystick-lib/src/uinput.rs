@@ -1,9 +1,21 @@
+use std::collections::{HashMap, HashSet};
+
 use evdev::{
-    uinput::{VirtualDevice, VirtualDeviceBuilder},
-    AbsoluteAxisType, AttributeSet, EventType, InputEvent, InputEventKind, Key, RelativeAxisType,
+    uinput::{UinputAbsSetup, VirtualDevice, VirtualDeviceBuilder},
+    AbsInfo, AbsoluteAxisType, AttributeSet, EventType, InputEvent, InputEventKind, Key,
+    RelativeAxisType,
 };
 
-use crate::{JoystickConfig, MouseConfig};
+use crate::input_device::AxisCalibration;
+use crate::{ButtonAction, ButtonConfig, ClickAction, JoystickConfig, MouseConfig, OutputMode};
+
+fn keyboard_and_buttons() -> AttributeSet<Key> {
+    AttributeSet::from_iter(
+        (0..Key::KEY_MAX.code())
+            .map(Key::new)
+            .chain([Key::BTN_RIGHT, Key::BTN_LEFT, Key::BTN_MIDDLE]),
+    )
+}
 
 pub struct UInputMouse {
     device: VirtualDevice,
@@ -16,8 +28,10 @@ impl UInputMouse {
             .with_relative_axes(&AttributeSet::from_iter([
                 RelativeAxisType::REL_X,
                 RelativeAxisType::REL_Y,
+                RelativeAxisType::REL_WHEEL,
+                RelativeAxisType::REL_HWHEEL,
             ]))?
-            .with_keys(&AttributeSet::from_iter([Key::BTN_RIGHT, Key::BTN_LEFT]))?
+            .with_keys(&keyboard_and_buttons())?
             .build()?;
 
         Ok(Self { device })
@@ -32,10 +46,123 @@ impl UInputMouse {
         let input_event = InputEvent::new_now(EventType::RELATIVE, RelativeAxisType::REL_Y.0, y);
         self.device.emit(&[input_event])
     }
+
+    pub fn move_wheel_x(&mut self, x: i32) -> Result<(), std::io::Error> {
+        let input_event = InputEvent::new_now(EventType::RELATIVE, RelativeAxisType::REL_HWHEEL.0, x);
+        self.device.emit(&[input_event])
+    }
+
+    pub fn move_wheel_y(&mut self, y: i32) -> Result<(), std::io::Error> {
+        let input_event = InputEvent::new_now(EventType::RELATIVE, RelativeAxisType::REL_WHEEL.0, y);
+        self.device.emit(&[input_event])
+    }
+
+    pub fn emit_key(&mut self, key: Key, pressed: bool) -> Result<(), std::io::Error> {
+        let input_event = InputEvent::new_now(EventType::KEY, key.code(), pressed as i32);
+        self.device.emit(&[input_event])
+    }
+}
+
+/// An alternative output device for [`OutputMode::Absolute`]: instead of relative `REL_X`/`REL_Y`
+/// deltas it drives an `ABS_X`/`ABS_Y` pointer over a fixed virtual screen resolution.
+pub struct UInputAbsoluteMouse {
+    device: VirtualDevice,
+}
+
+impl UInputAbsoluteMouse {
+    pub fn new(screen_width: i32, screen_height: i32) -> Result<Self, std::io::Error> {
+        let x_axis = UinputAbsSetup::new(
+            AbsoluteAxisType::ABS_X,
+            AbsInfo::new(0, 0, screen_width, 0, 0, 0),
+        );
+        let y_axis = UinputAbsSetup::new(
+            AbsoluteAxisType::ABS_Y,
+            AbsInfo::new(0, 0, screen_height, 0, 0, 0),
+        );
+
+        let device = VirtualDeviceBuilder::new()?
+            .name("Virtual absolute mouse")
+            .with_absolute_axis(&x_axis)?
+            .with_absolute_axis(&y_axis)?
+            .with_keys(&keyboard_and_buttons())?
+            .build()?;
+
+        Ok(Self { device })
+    }
+
+    pub fn move_to(&mut self, x: i32, y: i32) -> Result<(), std::io::Error> {
+        self.device.emit(&[
+            InputEvent::new_now(EventType::ABSOLUTE, AbsoluteAxisType::ABS_X.0, x),
+            InputEvent::new_now(EventType::ABSOLUTE, AbsoluteAxisType::ABS_Y.0, y),
+        ])
+    }
+
+    pub fn emit_key(&mut self, key: Key, pressed: bool) -> Result<(), std::io::Error> {
+        let input_event = InputEvent::new_now(EventType::KEY, key.code(), pressed as i32);
+        self.device.emit(&[input_event])
+    }
+}
+
+enum Output {
+    Relative(UInputMouse),
+    Absolute(UInputAbsoluteMouse),
+}
+
+fn click_key(action: ClickAction) -> Key {
+    match action {
+        ClickAction::MouseLeft => Key::BTN_LEFT,
+        ClickAction::MouseRight => Key::BTN_RIGHT,
+        ClickAction::MouseMiddle => Key::BTN_MIDDLE,
+        ClickAction::Key(code) => Key::new(code),
+    }
+}
+
+fn key_for_action(action: ButtonAction) -> Option<Key> {
+    match action {
+        ButtonAction::MouseLeft => Some(Key::BTN_LEFT),
+        ButtonAction::MouseRight => Some(Key::BTN_RIGHT),
+        ButtonAction::MouseMiddle => Some(Key::BTN_MIDDLE),
+        ButtonAction::Key(code) => Some(Key::new(code)),
+        ButtonAction::Turbo { .. } | ButtonAction::DoubleClick { .. } => None,
+    }
+}
+
+/// An output action queued to fire at a later `Instant`; drained once per `send_event` tick.
+struct ScheduledEvent {
+    fire_at: std::time::Instant,
+    action: ScheduledAction,
+}
+
+enum ScheduledAction {
+    Emit { key: Key, pressed: bool },
+    /// Re-armed by itself every `interval` for as long as `source` stays in `VMouseManager::pressed`.
+    TurboTick {
+        source: Key,
+        target: Key,
+        interval: std::time::Duration,
+    },
+}
+
+impl PartialEq for ScheduledEvent {
+    fn eq(&self, other: &Self) -> bool {
+        self.fire_at == other.fire_at
+    }
+}
+impl Eq for ScheduledEvent {}
+impl PartialOrd for ScheduledEvent {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for ScheduledEvent {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // Reversed so a `BinaryHeap` (a max-heap) pops the earliest deadline first.
+        other.fire_at.cmp(&self.fire_at)
+    }
 }
 
 pub struct VMouseManager {
-    vmouse: UInputMouse,
+    output: Output,
     ddx: f32,
     ddy: f32,
     dx: f32,
@@ -43,12 +170,35 @@ pub struct VMouseManager {
     speed_multiplier: f32,
     val_x: f32,
     val_y: f32,
+    val_rx: f32,
+    val_ry: f32,
+    /// Which axis code last wrote `val_rx`/`val_ry` (`ABS_RX`/`ABS_Z` and `ABS_RY`/`ABS_RZ`
+    /// respectively), so calibration is looked up under the axis the controller actually uses.
+    val_rx_axis: AbsoluteAxisType,
+    val_ry_axis: AbsoluteAxisType,
+    ddwx: f32,
+    ddwy: f32,
+    dwx: f32,
+    dwy: f32,
+    scroll_speed: f32,
+    screen_width: i32,
+    screen_height: i32,
+    pressed: HashSet<Key>,
+    queue: std::collections::BinaryHeap<ScheduledEvent>,
 }
 
 impl VMouseManager {
     pub fn new(config: &MouseConfig) -> Result<Self, std::io::Error> {
+        let output = match config.mode {
+            OutputMode::Relative => Output::Relative(UInputMouse::new()?),
+            OutputMode::Absolute => Output::Absolute(UInputAbsoluteMouse::new(
+                config.screen_width,
+                config.screen_height,
+            )?),
+        };
+
         Ok(Self {
-            vmouse: UInputMouse::new()?,
+            output,
             ddx: 0.0,
             ddy: 0.0,
             speed_multiplier: config.speed,
@@ -56,49 +206,251 @@ impl VMouseManager {
             dy: 0.0,
             val_x: 0.0,
             val_y: 0.0,
+            val_rx: 0.0,
+            val_ry: 0.0,
+            val_rx_axis: AbsoluteAxisType::ABS_RX,
+            val_ry_axis: AbsoluteAxisType::ABS_RY,
+            ddwx: 0.0,
+            ddwy: 0.0,
+            dwx: 0.0,
+            dwy: 0.0,
+            scroll_speed: config.scroll_speed,
+            screen_width: config.screen_width,
+            screen_height: config.screen_height,
+            pressed: HashSet::new(),
+            queue: std::collections::BinaryHeap::new(),
         })
     }
 
-    pub fn map_event(&mut self, event: InputEvent, joystick_config: &JoystickConfig) {
-        let convert = |value: f32| -> f32 {
-            let fcentered = value - joystick_config.offset;
+    fn emit(&mut self, key: Key, pressed: bool) -> Result<(), std::io::Error> {
+        match &mut self.output {
+            Output::Relative(vmouse) => vmouse.emit_key(key, pressed),
+            Output::Absolute(vmouse) => vmouse.emit_key(key, pressed),
+        }
+    }
+
+    /// Drain every queued action whose deadline has passed. Turbo actions re-arm themselves here
+    /// for as long as their source button is still held.
+    fn drain_scheduled(&mut self) {
+        let now = std::time::Instant::now();
+        while matches!(self.queue.peek(), Some(scheduled) if scheduled.fire_at <= now) {
+            let scheduled = self.queue.pop().unwrap();
+            match scheduled.action {
+                ScheduledAction::Emit { key, pressed } => {
+                    if let Err(err) = self.emit(key, pressed) {
+                        eprintln!("Error while emitting scheduled button event: {err}");
+                    }
+                }
+                ScheduledAction::TurboTick {
+                    source,
+                    target,
+                    interval,
+                } => {
+                    if self.pressed.contains(&source) {
+                        if let Err(err) = self.emit(target, true) {
+                            eprintln!("Error while emitting scheduled button event: {err}");
+                        }
+                        self.queue.push(ScheduledEvent {
+                            fire_at: now + interval / 2,
+                            action: ScheduledAction::Emit {
+                                key: target,
+                                pressed: false,
+                            },
+                        });
+                        self.queue.push(ScheduledEvent {
+                            fire_at: now + interval,
+                            action: ScheduledAction::TurboTick {
+                                source,
+                                target,
+                                interval,
+                            },
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    fn map_button_event(&mut self, key: Key, pressed: bool, button_config: &ButtonConfig) {
+        // Only mirror real press/release transitions, not key-repeat (EV_KEY value 2).
+        let changed = if pressed {
+            self.pressed.insert(key)
+        } else {
+            self.pressed.remove(&key)
+        };
+        if !changed {
+            return;
+        }
+
+        for mapping in &button_config.mappings {
+            if mapping.source != key.code() {
+                continue;
+            }
+
+            match mapping.action {
+                ButtonAction::Turbo {
+                    action,
+                    interval_ms,
+                } => {
+                    if pressed {
+                        self.queue.push(ScheduledEvent {
+                            fire_at: std::time::Instant::now(),
+                            action: ScheduledAction::TurboTick {
+                                source: key,
+                                target: click_key(action),
+                                interval: std::time::Duration::from_millis(interval_ms),
+                            },
+                        });
+                    }
+                }
+                ButtonAction::DoubleClick { action, delay_ms } => {
+                    if pressed {
+                        let target = click_key(action);
+                        if let Err(err) = self.emit(target, true) {
+                            eprintln!("Error while emitting mouse button: {err}");
+                        }
+                        if let Err(err) = self.emit(target, false) {
+                            eprintln!("Error while emitting mouse button: {err}");
+                        }
+
+                        let now = std::time::Instant::now();
+                        let delay = std::time::Duration::from_millis(delay_ms);
+                        self.queue.push(ScheduledEvent {
+                            fire_at: now + delay,
+                            action: ScheduledAction::Emit {
+                                key: target,
+                                pressed: true,
+                            },
+                        });
+                        self.queue.push(ScheduledEvent {
+                            fire_at: now + delay + std::time::Duration::from_millis(30),
+                            action: ScheduledAction::Emit {
+                                key: target,
+                                pressed: false,
+                            },
+                        });
+                    }
+                }
+                simple => {
+                    if let Some(target) = key_for_action(simple) {
+                        if let Err(err) = self.emit(target, pressed) {
+                            eprintln!("Error while emitting mouse button: {err}");
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn map_event(
+        &mut self,
+        event: InputEvent,
+        joystick_config: &JoystickConfig,
+        button_config: &ButtonConfig,
+        axis_calibration: &HashMap<AbsoluteAxisType, AxisCalibration>,
+    ) {
+        let convert = |value: f32, axis: AbsoluteAxisType| -> f32 {
+            let calib = axis_calibration.get(&axis).copied().unwrap_or_default();
+            let offset = joystick_config.offset.unwrap_or(calib.center);
+            let dead_zone = joystick_config.dead_zone.unwrap_or(calib.dead_zone);
+            let half_amplitude = joystick_config
+                .half_amplitude
+                .unwrap_or(calib.half_amplitude);
+
+            let fcentered = value - offset;
             let sign = fcentered.signum();
             let fabs = fcentered.abs();
 
-            let fabs = (fabs - joystick_config.dead_zone)
-                / (joystick_config.half_amplitude - joystick_config.dead_zone);
+            let fabs = (fabs - dead_zone) / (half_amplitude - dead_zone);
 
             sign * fabs.clamp(0.0, 1.0)
         };
         match event.kind() {
             InputEventKind::AbsAxis(AbsoluteAxisType::ABS_X) => self.val_x = event.value() as f32,
             InputEventKind::AbsAxis(AbsoluteAxisType::ABS_Y) => self.val_y = event.value() as f32,
+            InputEventKind::AbsAxis(axis @ (AbsoluteAxisType::ABS_RX | AbsoluteAxisType::ABS_Z)) => {
+                self.val_rx = event.value() as f32;
+                self.val_rx_axis = axis;
+            }
+            InputEventKind::AbsAxis(axis @ (AbsoluteAxisType::ABS_RY | AbsoluteAxisType::ABS_RZ)) => {
+                self.val_ry = event.value() as f32;
+                self.val_ry_axis = axis;
+            }
+            InputEventKind::Key(key) => {
+                self.map_button_event(key, event.value() != 0, button_config)
+            }
+            InputEventKind::Synchronization(evdev::Synchronization::SYN_DROPPED) => {
+                eprintln!("SYN_DROPPED: evdev is resyncing, axis state will be corrected in-stream");
+            }
             _ => (),
         }
 
+        // `InputDevice::events` comes from `Device::fetch_events`, which already replays the
+        // authoritative ABS_X/ABS_Y values as synthetic events after a SYN_DROPPED, so val_x/val_y
+        // above are corrected in-stream and ddx/ddy below are recomputed from them on every call -
+        // no stale accumulation survives a dropped packet.
         let (sin, cos) = f32::sin_cos(std::f32::consts::PI / 180. * joystick_config.angle);
-        let v_x = convert(self.val_x);
-        let v_y = convert(self.val_y);
+        let v_x = convert(self.val_x, AbsoluteAxisType::ABS_X);
+        let v_y = convert(self.val_y, AbsoluteAxisType::ABS_Y);
         self.ddx = v_x * cos + v_y * sin;
         self.ddy = -v_x * sin + v_y * cos;
+
+        self.ddwx = convert(self.val_rx, self.val_rx_axis);
+        self.ddwy = convert(self.val_ry, self.val_ry_axis);
     }
 
     pub fn send_event(&mut self, dt: f32) -> Result<(), std::io::Error> {
+        self.drain_scheduled();
+
+        let Output::Relative(vmouse) = &mut self.output else {
+            return self.send_absolute_event();
+        };
+
         self.dx += dt * self.speed_multiplier * self.ddx;
         self.dy += dt * self.speed_multiplier * self.ddy;
 
         // println!("Move mouse with {dt} {} {}", self.ddx, self.ddy);
         if self.dx.abs() >= 1.0 {
             let dx = self.dx as i32;
-            self.vmouse.move_mouse_x(dx)?;
+            vmouse.move_mouse_x(dx)?;
             self.dx -= dx as f32;
         }
 
         if self.dy.abs() >= 1.0 {
             let dy = self.dy as i32;
-            self.vmouse.move_mouse_y(dy)?;
+            vmouse.move_mouse_y(dy)?;
             self.dy -= dy as f32;
         }
+
+        if self.scroll_speed != 0.0 {
+            self.dwx += dt * self.scroll_speed * self.ddwx;
+            self.dwy += dt * self.scroll_speed * self.ddwy;
+
+            if self.dwx.abs() >= 1.0 {
+                let dwx = self.dwx as i32;
+                vmouse.move_wheel_x(dwx)?;
+                self.dwx -= dwx as f32;
+            }
+
+            if self.dwy.abs() >= 1.0 {
+                let dwy = self.dwy as i32;
+                vmouse.move_wheel_y(dwy)?;
+                self.dwy -= dwy as f32;
+            }
+        }
         Ok(())
     }
+
+    fn send_absolute_event(&mut self) -> Result<(), std::io::Error> {
+        let Output::Absolute(vmouse) = &mut self.output else {
+            return Ok(());
+        };
+
+        let half_width = self.screen_width as f32 / 2.0;
+        let half_height = self.screen_height as f32 / 2.0;
+        let x = (half_width + self.ddx * half_width).clamp(0.0, self.screen_width as f32) as i32;
+        let y = (half_height + self.ddy * half_height).clamp(0.0, self.screen_height as f32) as i32;
+
+        vmouse.move_to(x, y)
+    }
 }
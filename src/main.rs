@@ -6,8 +6,8 @@ use std::{
 };
 
 use evdev_rs::{
-    enums::{EventCode, EV_ABS},
-    DeviceWrapper, ReadFlag,
+    enums::{int_to_ev_key, EventCode, EV_ABS, EV_KEY},
+    DeviceWrapper, ReadFlag, ReadStatus,
 };
 use mio::{unix::SourceFd, Events, Interest, Poll, Registry, Token};
 use udev::{MonitorBuilder, MonitorSocket};
@@ -17,6 +17,7 @@ fn process_udev_events(
     socket: &MonitorSocket,
     poll: &mut Poll,
     input_device_pool: &mut InputDevicePool,
+    device_config: &DeviceConfig,
 ) -> Result<(), std::io::Error> {
     for event in socket.iter() {
         match event.event_type() {
@@ -26,7 +27,7 @@ fn process_udev_events(
 
                 if device.sysname().to_str().unwrap().starts_with("event") {
                     println!("Device on devnode {:?} got added", devnode);
-                    input_device_pool.insert_from_path(poll, devnode.to_owned())?;
+                    input_device_pool.insert_from_path(poll, devnode.to_owned(), device_config)?;
                 }
             }
             udev::EventType::Remove => {
@@ -47,6 +48,7 @@ fn process_udev_events(
 struct InputDevice {
     path: PathBuf,
     device: evdev_rs::Device,
+    grabbed: bool,
 }
 
 impl InputDevice {
@@ -60,7 +62,25 @@ impl InputDevice {
                 device.product_id()
             );
         }
-        Ok(Self { path, device })
+
+        // Make the fd non-blocking so a `ReadFlag::NORMAL` read can never stall the poll loop;
+        // readiness still comes from mio, we just drain until `WouldBlock` instead of blocking.
+        let raw_fd = unsafe { evdev_sys::libevdev_get_fd(device.raw()) };
+        nix::fcntl::fcntl(raw_fd, nix::fcntl::FcntlArg::F_SETFL(nix::fcntl::OFlag::O_NONBLOCK))?;
+
+        Ok(Self {
+            path,
+            device,
+            grabbed: false,
+        })
+    }
+
+    /// Take exclusive ownership of the device (`EVIOCGRAB`) so its events stop propagating to the
+    /// rest of the system. Ungrabbed automatically on `Drop`.
+    pub fn grab(&mut self) -> Result<(), std::io::Error> {
+        self.device.grab(evdev_rs::GrabMode::Grab)?;
+        self.grabbed = true;
+        Ok(())
     }
 
     fn as_raw_fd(&self) -> std::os::fd::RawFd {
@@ -70,20 +90,29 @@ impl InputDevice {
         unsafe { evdev_sys::libevdev_get_fd(evdev_ctx) }.as_raw_fd()
     }
 
-    fn next_event(&self) -> Result<Option<evdev_rs::InputEvent>, std::io::Error> {
-        // TODO: take care of EAGAIN
-        let next_event = self
-            .device
-            .next_event(ReadFlag::NORMAL | ReadFlag::BLOCKING);
-
-        match next_event {
-            Ok((_success, event)) => Ok(Some(event)),
+    fn next_event(
+        &self,
+        flags: ReadFlag,
+    ) -> Result<Option<(evdev_rs::ReadStatus, evdev_rs::InputEvent)>, std::io::Error> {
+        // The fd is non-blocking (see `new`), so EAGAIN/WouldBlock here just means the caller
+        // has drained everything currently pending and should go back to `poll`.
+        match self.device.next_event(flags) {
+            Ok(result) => Ok(Some(result)),
             Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => Ok(None),
             Err(err) => Err(err),
         }
     }
 }
 
+impl Drop for InputDevice {
+    fn drop(&mut self) {
+        if self.grabbed {
+            // Best-effort: we're closing the fd right after anyway.
+            let _ = self.device.grab(evdev_rs::GrabMode::Ungrab);
+        }
+    }
+}
+
 impl mio::event::Source for InputDevice {
     fn register(
         &mut self,
@@ -144,8 +173,17 @@ impl InputDevicePool {
         &mut self,
         poll: &mut Poll,
         path: PathBuf,
+        device_config: &DeviceConfig,
     ) -> Result<(), std::io::Error> {
-        let device = InputDevice::new(path)?;
+        let mut device = InputDevice::new(path)?;
+        if !device_is_allowed(&device.device, device_config) {
+            println!("Ignoring device '{:?}' (filtered out by config)", device.path);
+            return Ok(());
+        }
+        if device_config.grab {
+            device.grab()?;
+        }
+
         let token = self.next_free_token();
         self.devices.push(device);
         poll.registry().register(
@@ -189,6 +227,52 @@ impl InputDevicePool {
     }
 }
 
+/// An output action deferred to a future point in time, ordered by `fire_at` so `VMouseManager`'s
+/// queue is a min-heap (soonest deadline first) despite `BinaryHeap` being a max-heap.
+struct ScheduledEvent {
+    fire_at: std::time::Instant,
+    action: ScheduledAction,
+}
+
+enum ScheduledAction {
+    Emit { button: EV_KEY, pressed: bool },
+    /// Re-fires every `interval` for as long as `source` is still held, emitting a full
+    /// press/release pulse of `button` each time.
+    TurboTick {
+        source: u16,
+        button: EV_KEY,
+        interval: std::time::Duration,
+    },
+}
+
+impl PartialEq for ScheduledEvent {
+    fn eq(&self, other: &Self) -> bool {
+        self.fire_at == other.fire_at
+    }
+}
+
+impl Eq for ScheduledEvent {}
+
+impl PartialOrd for ScheduledEvent {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ScheduledEvent {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other.fire_at.cmp(&self.fire_at)
+    }
+}
+
+fn click_key(action: ClickAction) -> EV_KEY {
+    match action {
+        ClickAction::MouseLeft => EV_KEY::BTN_LEFT,
+        ClickAction::MouseRight => EV_KEY::BTN_RIGHT,
+        ClickAction::MouseMiddle => EV_KEY::BTN_MIDDLE,
+    }
+}
+
 struct VMouseManager {
     vmouse: UInputMouse,
     ddx: f32,
@@ -198,6 +282,17 @@ struct VMouseManager {
     speed_multiplier: f32,
     val_x: f32,
     val_y: f32,
+    ddwx: f32,
+    ddwy: f32,
+    dwx: f32,
+    dwy: f32,
+    scroll_speed: f32,
+    val_rx: f32,
+    val_ry: f32,
+    /// Source button codes (from [`ButtonMapping::source`]) currently held down, so press/release
+    /// pairs are mirrored faithfully instead of re-firing on every key-repeat event.
+    pressed: std::collections::HashSet<u16>,
+    queue: std::collections::BinaryHeap<ScheduledEvent>,
 }
 
 impl VMouseManager {
@@ -211,11 +306,69 @@ impl VMouseManager {
             dy: 0.0,
             val_x: 0.0,
             val_y: 0.0,
+            ddwx: 0.0,
+            ddwy: 0.0,
+            dwx: 0.0,
+            dwy: 0.0,
+            scroll_speed: config.scroll_speed,
+            val_rx: 0.0,
+            val_ry: 0.0,
+            pressed: std::collections::HashSet::new(),
+            queue: std::collections::BinaryHeap::new(),
         })
     }
 
-    pub fn map_event(&mut self, event: evdev_rs::InputEvent, joystick_config: &JoystickConfig) {
-        let convert = |value: f32| -> f32 {
+    /// The soonest pending [`ScheduledEvent`] deadline, if any, so the caller can cap the `poll`
+    /// timeout and have timed actions fire promptly instead of waiting out the next 10ms frame.
+    fn next_deadline(&self) -> Option<std::time::Instant> {
+        self.queue.peek().map(|event| event.fire_at)
+    }
+
+    /// Emit every scheduled action whose deadline has passed, re-arming `TurboTick`s still held.
+    fn drain_scheduled(&mut self) {
+        let now = std::time::Instant::now();
+        while self.queue.peek().is_some_and(|event| event.fire_at <= now) {
+            let ScheduledEvent { action, .. } = self.queue.pop().unwrap();
+            match action {
+                ScheduledAction::Emit { button, pressed } => {
+                    if let Err(err) = self.vmouse.emit_button(button, pressed) {
+                        eprintln!("Error while emitting scheduled button event: {err}");
+                    }
+                }
+                ScheduledAction::TurboTick {
+                    source,
+                    button,
+                    interval,
+                } => {
+                    if !self.pressed.contains(&source) {
+                        continue;
+                    }
+                    if let Err(err) = self.vmouse.emit_button(button, true) {
+                        eprintln!("Error while emitting scheduled button event: {err}");
+                    }
+                    if let Err(err) = self.vmouse.emit_button(button, false) {
+                        eprintln!("Error while emitting scheduled button event: {err}");
+                    }
+                    self.queue.push(ScheduledEvent {
+                        fire_at: now + interval,
+                        action: ScheduledAction::TurboTick {
+                            source,
+                            button,
+                            interval,
+                        },
+                    });
+                }
+            }
+        }
+    }
+
+    pub fn map_event(
+        &mut self,
+        event: evdev_rs::InputEvent,
+        joystick_config: &JoystickConfig,
+        button_config: &ButtonConfig,
+    ) {
+        let normalize = |value: f32| -> f32 {
             let fcentered = value - joystick_config.offset;
             let sign = fcentered.signum();
             let fabs = fcentered.abs();
@@ -225,18 +378,127 @@ impl VMouseManager {
 
             sign * fabs.clamp(0.0, 1.0)
         };
+        let curve = |magnitude: f32| -> f32 {
+            magnitude.clamp(0.0, 1.0).powf(joystick_config.exponent)
+        };
         match event.event_code {
             EventCode::EV_ABS(EV_ABS::ABS_X) => self.val_x = event.value as f32,
             EventCode::EV_ABS(EV_ABS::ABS_Y) => self.val_y = event.value as f32,
+            EventCode::EV_ABS(EV_ABS::ABS_RX) => self.val_rx = event.value as f32,
+            EventCode::EV_ABS(EV_ABS::ABS_RY) => self.val_ry = event.value as f32,
+            EventCode::EV_KEY(key) => self.map_button_event(key, event.value != 0, button_config),
             _ => (),
         }
 
+        // Apply the response curve to the true 2D magnitude of the (rotated) stick vector, not to
+        // each rotated axis independently, so it scales speed without skewing diagonal direction.
         let (sin, cos) = f32::sin_cos(std::f32::consts::PI / 180. * joystick_config.angle);
-        self.ddx = convert(self.val_x * cos + self.val_y * sin);
-        self.ddy = convert(-self.val_x * sin + self.val_y * cos);
+        let nx = normalize(self.val_x);
+        let ny = normalize(self.val_y);
+        let rx = nx * cos + ny * sin;
+        let ry = -nx * sin + ny * cos;
+        let magnitude = rx.hypot(ry);
+        if magnitude > 0.0 {
+            let scale = curve(magnitude) / magnitude;
+            self.ddx = rx * scale;
+            self.ddy = ry * scale;
+        } else {
+            self.ddx = 0.0;
+            self.ddy = 0.0;
+        }
+
+        let wx = normalize(self.val_rx);
+        let wy = normalize(self.val_ry);
+        self.ddwx = wx.signum() * curve(wx.abs());
+        self.ddwy = wy.signum() * curve(wy.abs());
+    }
+
+    /// Translate a controller button press/release into the output action declared for it in
+    /// `button_config`, ignoring key-repeat by only acting on an actual pressed/released edge.
+    fn map_button_event(&mut self, key: EV_KEY, pressed: bool, button_config: &ButtonConfig) {
+        for mapping in &button_config.mappings {
+            if int_to_ev_key(mapping.source as u32) != Some(key) {
+                continue;
+            }
+
+            let changed = if pressed {
+                self.pressed.insert(mapping.source)
+            } else {
+                self.pressed.remove(&mapping.source)
+            };
+            if !changed {
+                continue;
+            }
+
+            match mapping.action {
+                ButtonAction::MouseLeft => {
+                    if let Err(err) = self.vmouse.emit_button(EV_KEY::BTN_LEFT, pressed) {
+                        eprintln!("Error while emitting mouse button: {err}");
+                    }
+                }
+                ButtonAction::MouseRight => {
+                    if let Err(err) = self.vmouse.emit_button(EV_KEY::BTN_RIGHT, pressed) {
+                        eprintln!("Error while emitting mouse button: {err}");
+                    }
+                }
+                ButtonAction::MouseMiddle => {
+                    if let Err(err) = self.vmouse.emit_button(EV_KEY::BTN_MIDDLE, pressed) {
+                        eprintln!("Error while emitting mouse button: {err}");
+                    }
+                }
+                ButtonAction::Turbo {
+                    action,
+                    interval_ms,
+                } => {
+                    // Arm the first tick for "now" rather than emitting a press here and a
+                    // `TurboTick` only `interval_ms` later: that would hold the first click down
+                    // for a full interval before its first release, unlike every pulse after it.
+                    if pressed {
+                        self.queue.push(ScheduledEvent {
+                            fire_at: std::time::Instant::now(),
+                            action: ScheduledAction::TurboTick {
+                                source: mapping.source,
+                                button: click_key(action),
+                                interval: std::time::Duration::from_millis(interval_ms),
+                            },
+                        });
+                    }
+                }
+                ButtonAction::DoubleClick { action, delay_ms } => {
+                    if !pressed {
+                        continue;
+                    }
+                    let button = click_key(action);
+                    if let Err(err) = self.vmouse.emit_button(button, true) {
+                        eprintln!("Error while emitting mouse button: {err}");
+                    }
+                    if let Err(err) = self.vmouse.emit_button(button, false) {
+                        eprintln!("Error while emitting mouse button: {err}");
+                    }
+                    let delay = std::time::Duration::from_millis(delay_ms);
+                    let now = std::time::Instant::now();
+                    self.queue.push(ScheduledEvent {
+                        fire_at: now + delay,
+                        action: ScheduledAction::Emit {
+                            button,
+                            pressed: true,
+                        },
+                    });
+                    self.queue.push(ScheduledEvent {
+                        fire_at: now + delay + std::time::Duration::from_millis(50),
+                        action: ScheduledAction::Emit {
+                            button,
+                            pressed: false,
+                        },
+                    });
+                }
+            }
+        }
     }
 
     fn send_event(&mut self, dt: f32) -> Result<(), std::io::Error> {
+        self.drain_scheduled();
+
         self.dx += dt * self.speed_multiplier * self.ddx;
         self.dy += dt * self.speed_multiplier * self.ddy;
 
@@ -252,34 +514,159 @@ impl VMouseManager {
             self.vmouse.move_mouse_y(dy)?;
             self.dy -= dy as f32;
         }
+
+        self.dwx += dt * self.scroll_speed * self.ddwx;
+        self.dwy += dt * self.scroll_speed * self.ddwy;
+
+        if self.dwx.abs() >= 1.0 {
+            let dwx = self.dwx as i32;
+            self.vmouse.move_wheel_x(dwx)?;
+            self.dwx -= dwx as f32;
+        }
+
+        if self.dwy.abs() >= 1.0 {
+            let dwy = self.dwy as i32;
+            self.vmouse.move_wheel_y(dwy)?;
+            self.dwy -= dwy as f32;
+        }
         Ok(())
     }
 }
 
+/// `vendor_id:product_id` of the uinput device this process creates itself (see
+/// `uinput::UInputMouse::new`). Always excluded from capture, regardless of `allow`/`ignore`, so a
+/// user enabling `grab` on their controller never ends up grabbing the tool's own output device
+/// and silently cutting off the synthesized clicks/motion.
+const SELF_VENDOR_PRODUCT: &str = "abcd:efef";
+
+/// Whether `device` should be captured: it must match an `allow` entry (if any are configured)
+/// and must not match any `ignore` entry, matched against the device name (substring) and its
+/// `vendor_id:product_id`.
+fn device_is_allowed(device: &evdev_rs::Device, config: &DeviceConfig) -> bool {
+    let name = device.name().unwrap_or_default();
+    let ids = format!("{:04x}:{:04x}", device.vendor_id(), device.product_id());
+
+    if ids == SELF_VENDOR_PRODUCT {
+        return false;
+    }
+
+    let matches = |pattern: &String| name.contains(pattern.as_str()) || &ids == pattern;
+
+    if config.ignore.iter().any(matches) {
+        return false;
+    }
+    config.allow.is_empty() || config.allow.iter().any(matches)
+}
+
+/// `/dev/input/by-id` symlinks ending in `-event-joystick` give a stable identity for joystick
+/// nodes instead of racing the kernel-assigned `eventN` numbers, so prefer them when present.
+fn joystick_paths_by_id() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir("/dev/input/by-id/") else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.ends_with("-event-joystick"))
+        })
+        .filter_map(|entry| std::fs::canonicalize(entry.path()).ok())
+        .collect()
+}
+
 fn populate_from_dev_input(
     input_device_pool: &mut InputDevicePool,
     poll: &mut Poll,
+    device_config: &DeviceConfig,
 ) -> std::io::Result<()> {
+    let mut seen = std::collections::HashSet::new();
+
+    for path in joystick_paths_by_id() {
+        seen.insert(path.clone());
+        input_device_pool.insert_from_path(poll, path, device_config)?;
+    }
+
     for entry in std::fs::read_dir("/dev/input/").unwrap() {
         let entry = entry?;
         if entry.file_type().unwrap().is_char_device()
             && entry.file_name().to_str().unwrap().starts_with("event")
         {
-            input_device_pool.insert_from_path(poll, entry.path().to_owned())?;
+            let path = entry.path();
+            if seen.contains(&path) {
+                continue;
+            }
+            input_device_pool.insert_from_path(poll, path, device_config)?;
         }
     }
     Ok(())
 }
 
+#[derive(serde::Deserialize, Default)]
+struct DeviceConfig {
+    #[serde(default)]
+    allow: Vec<String>,
+    #[serde(default)]
+    ignore: Vec<String>,
+    /// Take exclusive ownership (`EVIOCGRAB`) of every captured device so its events stop
+    /// reaching the rest of the system (games, the desktop) once this tool is mapping it.
+    #[serde(default)]
+    grab: bool,
+}
+
 #[derive(serde::Deserialize)]
 struct Config {
     mouse: MouseConfig,
     joystick: JoystickConfig,
+    #[serde(default)]
+    device: DeviceConfig,
+    #[serde(default)]
+    buttons: ButtonConfig,
 }
 
 #[derive(serde::Deserialize)]
 struct MouseConfig {
     speed: f32,
+    /// Speed applied to the right stick when driving `REL_WHEEL`/`REL_HWHEEL`; `0.0` disables it.
+    #[serde(default)]
+    scroll_speed: f32,
+}
+
+#[derive(serde::Deserialize, Default)]
+struct ButtonConfig {
+    #[serde(default)]
+    mappings: Vec<ButtonMapping>,
+}
+
+/// A single source button (by evdev `EV_KEY` code) mapped to an output mouse click.
+#[derive(serde::Deserialize, Clone, Copy)]
+struct ButtonMapping {
+    source: u16,
+    action: ButtonAction,
+}
+
+#[derive(serde::Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum ButtonAction {
+    MouseLeft,
+    MouseRight,
+    MouseMiddle,
+    /// Repeatedly press and release `action` at `interval_ms` for as long as the source button
+    /// stays held.
+    Turbo { action: ClickAction, interval_ms: u64 },
+    /// Press and release `action`, then do it again after `delay_ms`.
+    DoubleClick { action: ClickAction, delay_ms: u64 },
+}
+
+/// The click a [`ButtonAction::Turbo`] or [`ButtonAction::DoubleClick`] repeats.
+#[derive(serde::Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum ClickAction {
+    MouseLeft,
+    MouseRight,
+    MouseMiddle,
 }
 
 #[derive(serde::Deserialize)]
@@ -288,18 +675,33 @@ struct JoystickConfig {
     pub max: f32,
     pub offset: f32,
     pub angle: f32,
+    /// Power/gamma curve applied to the normalized magnitude after the dead-zone: `out =
+    /// m.powf(exponent)`. `1.0` (the default) is linear; `2.0`-`3.0` gives finer low-end control
+    /// for precise slow movement while still reaching full speed at full deflection.
+    #[serde(default = "default_exponent")]
+    pub exponent: f32,
+}
+
+fn default_exponent() -> f32 {
+    1.0
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
-            mouse: MouseConfig { speed: 700.0 },
+            mouse: MouseConfig {
+                speed: 700.0,
+                scroll_speed: 0.0,
+            },
             joystick: JoystickConfig {
                 dead_zone: 0.0,
                 max: i16::MAX as f32,
                 offset: 0.0,
                 angle: 0.0,
+                exponent: default_exponent(),
             },
+            device: DeviceConfig::default(),
+            buttons: ButtonConfig::default(),
         }
     }
 }
@@ -339,7 +741,8 @@ fn main() {
         .expect("Could not register udev socket for polling");
 
     let mut input_device_pool = InputDevicePool::new(1);
-    populate_from_dev_input(&mut input_device_pool, &mut poll).expect("Can't populate");
+    populate_from_dev_input(&mut input_device_pool, &mut poll, &config.device)
+        .expect("Can't populate");
 
     let mut vmouse_manager = VMouseManager::new(config.mouse).expect("Can't create vmouse");
 
@@ -350,17 +753,26 @@ fn main() {
         last = now;
 
         // NOTE: poll rate is 100HZ, maybe not the best ?
-        poll.poll(&mut events, Some(std::time::Duration::from_millis(10)))
-            .expect("Could not poll");
+        let frame = std::time::Duration::from_millis(10);
+        let timeout = vmouse_manager
+            .next_deadline()
+            .map(|deadline| deadline.saturating_duration_since(now).min(frame))
+            .unwrap_or(frame);
+        poll.poll(&mut events, Some(timeout)).expect("Could not poll");
 
         for event in &events {
             match event.token() {
-                Token(0) => process_udev_events(&udev_socket, &mut poll, &mut input_device_pool)
-                    .expect("Error while processing udev events"),
+                Token(0) => process_udev_events(
+                    &udev_socket,
+                    &mut poll,
+                    &mut input_device_pool,
+                    &config.device,
+                )
+                .expect("Error while processing udev events"),
                 token if input_device_pool.contains(token) => {
                     let Some(device) = input_device_pool.get(token) else {break;};
                     loop {
-                        let event = device.next_event();
+                        let event = device.next_event(ReadFlag::NORMAL);
 
                         match event {
                             Err(err) => {
@@ -368,8 +780,30 @@ fn main() {
                                     "unexpected error while getting input device next event: {err}"
                                 );
                             }
-                            Ok(Some(event)) => {
-                                vmouse_manager.map_event(event, &config.joystick);
+                            Ok(Some((ReadStatus::Success, event))) => {
+                                vmouse_manager.map_event(event, &config.joystick, &config.buttons);
+                                continue;
+                            }
+                            Ok(Some((ReadStatus::Sync, event))) => {
+                                // The kernel dropped events out from under us; replay the
+                                // resync events libevdev synthesizes so our cached axis state
+                                // (val_x/val_y, ...) catches back up to reality instead of
+                                // drifting on the stale pre-drop values.
+                                vmouse_manager.map_event(event, &config.joystick, &config.buttons);
+                                loop {
+                                    match device.next_event(ReadFlag::SYNC) {
+                                        Ok(Some((_, event))) => vmouse_manager.map_event(
+                                            event,
+                                            &config.joystick,
+                                            &config.buttons,
+                                        ),
+                                        Ok(None) => break,
+                                        Err(err) => {
+                                            eprintln!("error while resyncing input device: {err}");
+                                            break;
+                                        }
+                                    }
+                                }
                                 continue;
                             }
                             Ok(None) => (),
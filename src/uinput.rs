@@ -23,10 +23,13 @@ impl UInputMouse {
         u.enable_event_type(&EventType::EV_KEY)?;
         u.enable_event_code(&EventCode::EV_KEY(EV_KEY::BTN_LEFT), None)?;
         u.enable_event_code(&EventCode::EV_KEY(EV_KEY::BTN_RIGHT), None)?;
+        u.enable_event_code(&EventCode::EV_KEY(EV_KEY::BTN_MIDDLE), None)?;
 
         u.enable_event_type(&EventType::EV_REL)?;
         u.enable_event_code(&EventCode::EV_REL(EV_REL::REL_X), None)?;
         u.enable_event_code(&EventCode::EV_REL(EV_REL::REL_Y), None)?;
+        u.enable_event_code(&EventCode::EV_REL(EV_REL::REL_WHEEL), None)?;
+        u.enable_event_code(&EventCode::EV_REL(EV_REL::REL_HWHEEL), None)?;
 
         u.enable_event_code(&EventCode::EV_SYN(EV_SYN::SYN_REPORT), None)?;
 
@@ -42,6 +45,14 @@ impl UInputMouse {
         self.move_mouse(EV_REL::REL_Y, y)
     }
 
+    pub fn move_wheel_x(&mut self, x: i32) -> Result<(), std::io::Error> {
+        self.move_mouse(EV_REL::REL_HWHEEL, x)
+    }
+
+    pub fn move_wheel_y(&mut self, y: i32) -> Result<(), std::io::Error> {
+        self.move_mouse(EV_REL::REL_WHEEL, y)
+    }
+
     // You doesnt NEED self to be mut, but i find it better for semantics
     fn move_mouse(&mut self, ev_rel: EV_REL, value: i32) -> Result<(), std::io::Error> {
         let time = TimeVal::try_from(std::time::SystemTime::now()).unwrap();
@@ -59,6 +70,24 @@ impl UInputMouse {
 
         Ok(())
     }
+
+    /// Emit a button press (`value` 1) or release (`value` 0) followed by a `SYN_REPORT`.
+    pub fn emit_button(&mut self, button: EV_KEY, pressed: bool) -> Result<(), std::io::Error> {
+        let time = TimeVal::try_from(std::time::SystemTime::now()).unwrap();
+        self.device.write_event(&InputEvent {
+            time,
+            event_code: EventCode::EV_KEY(button),
+            value: pressed as i32,
+        })?;
+
+        self.device.write_event(&InputEvent {
+            time,
+            event_code: EventCode::EV_SYN(EV_SYN::SYN_REPORT),
+            value: 0,
+        })?;
+
+        Ok(())
+    }
 }
 
 pub struct VMouseManager {